@@ -0,0 +1,106 @@
+/*
+ * Server-Sent Events so frontends stop polling `/check-payment` in a loop.
+ * `AppState` holds a couple of `broadcast` channels; whoever learns that an
+ * invoice settled (or expired) publishes to them, and these handlers just
+ * relay that to whichever clients are listening.
+ */
+
+use crate::models::Donation;
+use crate::AppState;
+use axum::extract::{Query, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use tokio::sync::broadcast;
+
+/// Published whenever a pending invoice settles or expires.
+#[derive(Clone, Serialize)]
+pub struct PaymentEvent {
+    pub payment_hash: String,
+    pub status: String,
+    pub paid_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+pub struct PaymentEventsQuery {
+    payment_hash: String,
+}
+
+/// `GET /payment-events?payment_hash=...` - streams a single event for the
+/// given invoice once it settles or expires, then closes.
+pub async fn payment_events(
+    Query(params): Query<PaymentEventsQuery>,
+    State(state): State<AppState>,
+) -> Sse<impl futures_util::Stream<Item = Result<Event, Infallible>>> {
+    let mut rx = state.payment_events.subscribe();
+    let target_hash = params.payment_hash;
+
+    let stream = async_stream::stream! {
+        loop {
+            match rx.recv().await {
+                Ok(event) if event.payment_hash == target_hash => {
+                    if let Ok(data) = serde_json::to_string(&event) {
+                        yield Ok(Event::default().event("payment").data(data));
+                    }
+                    break;
+                }
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    };
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// `GET /donation-feed` - streams every newly completed `Donation` as it
+/// happens, so a live campaign ticker can update without polling
+/// `/recent-donations`.
+pub async fn donation_feed(
+    State(state): State<AppState>,
+) -> Sse<impl futures_util::Stream<Item = Result<Event, Infallible>>> {
+    let mut rx = state.donation_feed.subscribe();
+
+    let stream = async_stream::stream! {
+        loop {
+            match rx.recv().await {
+                Ok(donation) => {
+                    if let Ok(data) = serde_json::to_string(&donation) {
+                        yield Ok(Event::default().event("donation").data(data));
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    };
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Publishes the payment-status event for `payment_hash`. The donation-feed
+/// event is sent separately, by `publish_donation`. Handlers that transition
+/// an invoice call this instead of poking the broadcast channels directly.
+pub fn publish_payment_settled(
+    state: &AppState,
+    payment_hash: &str,
+    status: &str,
+    paid_at: Option<DateTime<Utc>>,
+) {
+    let _ = state.payment_events.send(PaymentEvent {
+        payment_hash: payment_hash.to_string(),
+        status: status.to_string(),
+        paid_at,
+    });
+}
+
+/// Callers must only invoke this once per settled payment - every subscriber
+/// gets whatever is sent here verbatim, so a caller that re-publishes the
+/// same donation on a later tick (e.g. a poll loop that doesn't check
+/// whether it already recorded the payment) turns the feed into a duplicate
+/// ticker instead of a one-shot-per-donation stream.
+pub fn publish_donation(state: &AppState, donation: &Donation) {
+    let _ = state.donation_feed.send(donation.clone());
+}
@@ -0,0 +1,112 @@
+/*
+ * LNURL-pay (LUD-06 / LUD-16), so donors can use email-style Lightning
+ * addresses (donate@satsforgood.org) and let their wallet pick an amount
+ * instead of copying a raw BOLT11 string.
+ */
+
+use crate::lightning_backend::InvoiceDescription;
+use crate::{issue_invoice, AppState};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::Json;
+use serde::{Deserialize, Serialize};
+
+const MIN_SENDABLE_MSAT: u64 = 100 * 1000;
+const MAX_SENDABLE_MSAT: u64 = 1_000_000 * 1000;
+
+#[derive(Serialize)]
+pub struct LnurlPayParams {
+    callback: String,
+    #[serde(rename = "minSendable")]
+    min_sendable: u64,
+    #[serde(rename = "maxSendable")]
+    max_sendable: u64,
+    metadata: String,
+    tag: &'static str,
+}
+
+#[derive(Deserialize)]
+pub struct LnurlCallbackQuery {
+    /// Amount the wallet wants to pay, in millisatoshis (LUD-06).
+    amount: u64,
+    /// Mapped onto `donor_name` - LUD-12 lets wallets attach a comment.
+    comment: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct LnurlCallbackResponse {
+    pr: String,
+    routes: Vec<()>,
+}
+
+/// `GET /.well-known/lnurlp/:username` - LUD-06 well-known endpoint a wallet
+/// resolves when a donor enters `username@satsforgood.org`.
+pub async fn lnurlp_well_known(Path(username): Path<String>, State(state): State<AppState>) -> Json<LnurlPayParams> {
+    let base_url = public_base_url();
+    let metadata = build_metadata(&username);
+
+    // Touch state so this stays wired to AppState as per-recipient bounds
+    // (e.g. a custom min/max) are added later; today the bounds are global.
+    let _ = &state;
+
+    Json(LnurlPayParams {
+        callback: format!("{base_url}/lnurlp/callback?username={username}"),
+        min_sendable: MIN_SENDABLE_MSAT,
+        max_sendable: MAX_SENDABLE_MSAT,
+        metadata,
+        tag: "payRequest",
+    })
+}
+
+/// Builds the exact LUD-06 metadata string served from `/.well-known/lnurlp/:username`
+/// for `username` - `lnurlp_callback` hashes the same string into the invoice's
+/// `h` tag, so any drift between the two would make wallets reject the payment.
+fn build_metadata(username: &str) -> String {
+    let metadata_entries = vec![[
+        "text/plain".to_string(),
+        format!("Donation to {username} via SatsForGood"),
+    ]];
+    serde_json::to_string(&metadata_entries).unwrap_or_default()
+}
+
+#[derive(Deserialize)]
+pub struct LnurlCallbackParams {
+    username: Option<String>,
+}
+
+/// `GET /lnurlp/callback?amount=...&comment=...` - the wallet's second
+/// request, once the donor has picked an amount. Delegates to the same
+/// invoice-issuing path as `/create-invoice`.
+pub async fn lnurlp_callback(
+    Query(path_params): Query<LnurlCallbackParams>,
+    Query(params): Query<LnurlCallbackQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<LnurlCallbackResponse>, StatusCode> {
+    if params.amount < MIN_SENDABLE_MSAT || params.amount > MAX_SENDABLE_MSAT {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // The username must match whatever `/.well-known/lnurlp/:username` served,
+    // since the invoice's description_hash has to commit to that same metadata.
+    let username = path_params.username.ok_or(StatusCode::BAD_REQUEST)?;
+    let metadata = build_metadata(&username);
+
+    let amount_sats = params.amount / 1000;
+    let (invoice, _payment_hash, _qr_code) = issue_invoice(
+        &state,
+        amount_sats,
+        params.comment,
+        Some(username),
+        Some(InvoiceDescription::Hash(metadata)),
+    )
+    .await?;
+
+    Ok(Json(LnurlCallbackResponse {
+        pr: invoice,
+        routes: vec![],
+    }))
+}
+
+fn public_base_url() -> String {
+    std::env::var("PUBLIC_BASE_URL").unwrap_or_else(|_| "https://satsforgood.org".to_string())
+}
@@ -9,6 +9,13 @@
  * - Lightning node integration for payment verification
  */
 
+mod events;
+mod lightning_backend;
+mod lnurl;
+mod models;
+mod persistence;
+mod receipts;
+
 use axum::{
     extract::{Query, State},
     http::StatusCode,
@@ -19,67 +26,62 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc};
-use std::time::SystemTime;
-use tokio::sync::Mutex as AsyncMutex;
+use tokio::sync::{broadcast, Mutex as AsyncMutex};
 use tower_http::cors::CorsLayer;
 use chrono::Utc;
-use bitcoin::secp256k1::{Secp256k1, SecretKey};
 use bitcoin::hashes::{Hash, sha256};
-use lightning_invoice::{Currency, InvoiceBuilder};
-use rand::RngCore;
+use events::PaymentEvent;
+use lightning_backend::{InvoiceDescription, InvoiceState, LightningBackend, MockBackend};
+use models::{Donation, DonationStats, ForwardState, Offer, PaymentState, PendingInvoice, RecipientRoute};
+use persistence::{Persister, SqlitePersister};
 use uuid::Uuid;
 use hex;
 
+/// Capacity of the `payment_events`/`donation_feed` broadcast channels - a
+/// lagging SSE subscriber just misses the oldest buffered events, it never
+/// blocks a publisher.
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
 #[derive(Clone)]
-struct AppState {
+pub(crate) struct AppState {
     pending_invoices: Arc<AsyncMutex<HashMap<String, PendingInvoice>>>,
-    completed_donations: Arc<AsyncMutex<Vec<Donation>>>,
-    node_key: SecretKey,
+    active_offers: Arc<AsyncMutex<HashMap<String, Offer>>>,
+    backend: Arc<dyn LightningBackend>,
+    persister: Arc<dyn Persister>,
+    payment_events: broadcast::Sender<PaymentEvent>,
+    donation_feed: broadcast::Sender<Donation>,
+    /// Recipients registered to have their donations forwarded onward, keyed
+    /// by the same `recipient` label stored on `Donation`/`PendingInvoice`.
+    recipient_routes: Arc<AsyncMutex<HashMap<String, RecipientRoute>>>,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-struct PendingInvoice {
-    invoice: String,
-    payment_hash: String,
+#[derive(Deserialize)]
+struct CreateInvoiceRequest {
     amount_sats: u64,
     donor_name: Option<String>,
     recipient: Option<String>,
-    expires_at: chrono::DateTime<Utc>,
-    created_at: chrono::DateTime<Utc>,
-    payment_state: PaymentState,
-    paid_at: Option<chrono::DateTime<Utc>>,
-}
-
-#[derive(Clone, Serialize, Deserialize, PartialEq)]
-enum PaymentState {
-    Pending,    // Invoice created, waiting for payment
-    Paid,       // Payment confirmed on Lightning Network
-    Expired,    // Invoice expired without payment
 }
 
-#[derive(Clone, Serialize)]
-struct Donation {
-    id: String,
-    donor_name: String,
-    recipient: Option<String>,
-    amount_sats: u64,
+#[derive(Serialize)]
+struct CreateInvoiceResponse {
+    invoice: String,
     payment_hash: String,
-    paid_at: chrono::DateTime<Utc>,
+    qr_code: String,
+    expires_in: i64,
 }
 
 #[derive(Deserialize)]
-struct CreateInvoiceRequest {
-    amount_sats: u64,
-    donor_name: Option<String>,
+struct CreateOfferRequest {
+    /// Fixed donation amount in sats; omit for an "any amount" offer.
+    amount_sats: Option<u64>,
     recipient: Option<String>,
 }
 
 #[derive(Serialize)]
-struct CreateInvoiceResponse {
-    invoice: String,
-    payment_hash: String,
+struct CreateOfferResponse {
+    offer: String,
+    offer_id: String,
     qr_code: String,
-    expires_in: i64,
 }
 
 #[derive(Deserialize)]
@@ -93,52 +95,95 @@ struct CheckPaymentResponse {
     paid_at: Option<chrono::DateTime<Utc>>,
 }
 
-#[derive(Serialize)]
-struct DonationStats {
-    total_sats: u64,
-    donor_count: usize,
+#[derive(Deserialize)]
+struct RecentDonationsQuery {
+    offset: Option<u32>,
+    limit: Option<u32>,
 }
 
-#[derive(Serialize)]
-struct DonationReceipt {
-    id: String,
-    donor_name: String,
-    recipient: Option<String>,
-    amount_sats: u64,
-    payment_hash: String,
-    paid_at: chrono::DateTime<Utc>,
-    transaction_id: String,
-    network: String,
+#[derive(Deserialize)]
+struct RegisterRecipientRequest {
+    recipient: String,
+    /// A fixed-amount BOLT11 invoice to pay the recipient. Neither backend
+    /// implements keysend or BOLT12 dispatch yet, so a bare pubkey or offer
+    /// isn't accepted here - see `LightningBackend::send_payment`.
+    destination: String,
 }
 
-#[derive(Deserialize)]
-struct ReceiptRequest {
-    payment_hash: String,
+#[derive(Serialize)]
+struct RegisterRecipientResponse {
+    recipient: String,
+    registered: bool,
 }
 
 #[tokio::main]
 async fn main() {
     env_logger::init();
 
-    // Generate a proper node key for Lightning invoices
-    let _secp = Secp256k1::new();
-    let mut key_bytes = [0u8; 32];
-    rand::thread_rng().fill_bytes(&mut key_bytes);
-    let node_key = SecretKey::from_slice(&key_bytes).unwrap();
+    let backend: Arc<dyn LightningBackend> = build_backend();
+
+    let db_path = std::env::var("DATABASE_PATH").unwrap_or_else(|_| "satsforgood.sqlite3".to_string());
+    let persister: Arc<dyn Persister> =
+        Arc::new(SqlitePersister::open(&db_path).expect("failed to open SQLite database"));
+
+    // Reload non-expired pending invoices so in-flight donations survive a restart.
+    let pending_invoices = persister
+        .list_pending_invoices()
+        .await
+        .expect("failed to reload pending invoices")
+        .into_iter()
+        .filter(|invoice| invoice.expires_at > Utc::now())
+        .map(|invoice| (invoice.payment_hash.clone(), invoice))
+        .collect::<HashMap<_, _>>();
+    println!("💾 Reloaded {} pending invoice(s) from {}", pending_invoices.len(), db_path);
+
+    let active_offers = persister
+        .list_offers()
+        .await
+        .expect("failed to reload active offers")
+        .into_iter()
+        .map(|offer| (offer.offer_id.clone(), offer))
+        .collect::<HashMap<_, _>>();
+    println!("💾 Reloaded {} active offer(s) from {}", active_offers.len(), db_path);
+
+    let recipient_routes = persister
+        .list_recipient_routes()
+        .await
+        .expect("failed to reload recipient routes")
+        .into_iter()
+        .map(|route| (route.recipient.clone(), route))
+        .collect::<HashMap<_, _>>();
+    println!("💾 Reloaded {} recipient route(s) from {}", recipient_routes.len(), db_path);
+
+    let (payment_events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+    let (donation_feed, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
 
     let state = AppState {
-        pending_invoices: Arc::new(AsyncMutex::new(HashMap::new())),
-        completed_donations: Arc::new(AsyncMutex::new(Vec::new())),
-        node_key,
+        pending_invoices: Arc::new(AsyncMutex::new(pending_invoices)),
+        active_offers: Arc::new(AsyncMutex::new(active_offers)),
+        backend,
+        persister,
+        payment_events,
+        donation_feed,
+        recipient_routes: Arc::new(AsyncMutex::new(recipient_routes)),
     };
 
+    tokio::spawn(poll_offer_payments(state.clone()));
+    tokio::spawn(poll_pending_invoices(state.clone()));
+
     let app = Router::new()
         .route("/health", get(health_check))
         .route("/create-invoice", get(create_invoice))
+        .route("/create-offer", get(create_offer))
         .route("/check-payment", get(check_payment))
         .route("/donation-stats", get(get_donation_stats))
         .route("/recent-donations", get(get_recent_donations))
-        .route("/donation-receipt", get(get_donation_receipt))
+        .route("/donation-receipt", get(receipts::donation_receipt))
+        .route("/register-recipient", get(register_recipient))
+        .route("/payment-events", get(events::payment_events))
+        .route("/donation-feed", get(events::donation_feed))
+        .route("/.well-known/lnurlp/:username", get(lnurl::lnurlp_well_known))
+        .route("/lnurlp/callback", get(lnurl::lnurlp_callback))
         .layer(CorsLayer::permissive())
         .with_state(state);
 
@@ -151,336 +196,602 @@ async fn health_check() -> &'static str {
     "OK"
 }
 
+/// Picks a `LightningBackend` from the environment: `LND_REST_ENDPOINT` +
+/// `LND_MACAROON_HEX` + `LND_TLS_CERT_PATH` for LND, `CLN_RPC_SOCKET_PATH` for
+/// CLN, falling back to `MockBackend` (used by tests and local development)
+/// when neither is configured.
+fn build_backend() -> Arc<dyn LightningBackend> {
+    use lightning_backend::{ClnBackend, LndBackend};
+
+    if let (Ok(endpoint), Ok(macaroon_hex), Ok(tls_cert_path)) = (
+        std::env::var("LND_REST_ENDPOINT"),
+        std::env::var("LND_MACAROON_HEX"),
+        std::env::var("LND_TLS_CERT_PATH"),
+    ) {
+        let tls_cert = std::fs::read(&tls_cert_path).expect("failed to read LND_TLS_CERT_PATH");
+        let backend = LndBackend::new(endpoint, macaroon_hex, &tls_cert).expect("failed to configure LND backend");
+        return Arc::new(backend);
+    }
+
+    if let Ok(rpc_socket_path) = std::env::var("CLN_RPC_SOCKET_PATH") {
+        return Arc::new(ClnBackend::new(rpc_socket_path));
+    }
+
+    println!("⚠️ No Lightning node configured (LND_REST_ENDPOINT/CLN_RPC_SOCKET_PATH unset), using MockBackend");
+    Arc::new(MockBackend::new())
+}
+
 #[axum::debug_handler]
 async fn create_invoice(
     Query(params): Query<CreateInvoiceRequest>,
     State(state): State<AppState>,
 ) -> Result<Json<CreateInvoiceResponse>, StatusCode> {
+    let (invoice_str, payment_hash_hex, qr_code) = issue_invoice(
+        &state,
+        params.amount_sats,
+        params.donor_name,
+        params.recipient,
+        None,
+    )
+    .await?;
+
+    Ok(Json(CreateInvoiceResponse {
+        invoice: invoice_str,
+        payment_hash: payment_hash_hex,
+        qr_code,
+        expires_in: 3600,
+    }))
+}
+
+/// Shared invoice-issuing path behind `/create-invoice` and the LNURL-pay
+/// callback (`lnurl::lnurlp_callback`) - both just need a BOLT11 for a donation
+/// amount, they differ only in how the amount and donor info were collected.
+/// `description` is `None` for the plain-memo `/create-invoice` case; LNURL-pay
+/// passes `Some(InvoiceDescription::Hash(metadata))` so the invoice's `h` tag
+/// commits to the exact metadata served from `/.well-known/lnurlp`, per LUD-06.
+pub(crate) async fn issue_invoice(
+    state: &AppState,
+    amount_sats: u64,
+    donor_name: Option<String>,
+    recipient: Option<String>,
+    description: Option<InvoiceDescription>,
+) -> Result<(String, String, String), StatusCode> {
     // Validate donation amount
-    if params.amount_sats < 100 {
+    if !(100..=1_000_000).contains(&amount_sats) {
         return Err(StatusCode::BAD_REQUEST);
     }
-    if params.amount_sats > 1000000 {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
-    // Generate cryptographically secure payment hash
-    let mut payment_hash_bytes = [0u8; 32];
-    rand::thread_rng().fill_bytes(&mut payment_hash_bytes);
-    let payment_hash = sha256::Hash::from_slice(&payment_hash_bytes)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    // Use proper node key for signing
-    let secp = Secp256k1::new();
-    let pk = state.node_key.public_key(&secp);
-
-    // Create descriptive invoice message
-    let description = if let Some(ref recipient) = params.recipient {
-        format!("Donation of {} sats to {}", params.amount_sats, recipient)
-    } else {
-        format!("Donation of {} sats to SatsForGood", params.amount_sats)
-    };
-
-    // Create production-grade Lightning invoice
-    let raw_invoice = InvoiceBuilder::new(Currency::Bitcoin)
-        .amount_milli_satoshis(params.amount_sats * 1000)
-        .description(description)
-        .payment_hash(payment_hash)
-        .timestamp(SystemTime::now())
-        .min_final_cltv_expiry_delta(144)
-        .expiry_time(std::time::Duration::from_secs(3600)) // 1 hour expiry
-        .payee_pub_key(pk)
-        .build_raw()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let invoice = raw_invoice
-        .sign(|hash| Ok::<_, std::convert::Infallible>(secp.sign_ecdsa_recoverable(hash, &state.node_key)))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    let invoice_str = invoice.to_string();
+    // Create descriptive invoice message, unless the caller already has one.
+    let description = description.unwrap_or_else(|| {
+        InvoiceDescription::Memo(if let Some(ref recipient) = recipient {
+            format!("Donation of {} sats to {}", amount_sats, recipient)
+        } else {
+            format!("Donation of {} sats to SatsForGood", amount_sats)
+        })
+    });
+
+    // Delegate invoice creation to the configured Lightning node so the
+    // invoice is actually signed by (and payable to) a node that can receive.
+    let (invoice_str, payment_hash_hex) = state
+        .backend
+        .add_invoice(amount_sats * 1000, description, 3600)
+        .await
+        .map_err(|e| {
+            println!("⚠️ backend.add_invoice failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
     // Generate QR code optimized for mobile wallets
     let qr_code = generate_qr_base64(&invoice_str);
 
-    let payment_hash_hex = hex::encode(payment_hash);
     let expires_at = Utc::now() + chrono::Duration::hours(1);
 
     let pending_invoice = PendingInvoice {
         invoice: invoice_str.clone(),
         payment_hash: payment_hash_hex.clone(),
-        amount_sats: params.amount_sats,
-        donor_name: params.donor_name.clone(),
-        recipient: params.recipient.clone(),
+        payment_preimage: None,
+        amount_sats,
+        donor_name,
+        recipient,
         expires_at,
         created_at: Utc::now(),
         payment_state: PaymentState::Pending,
         paid_at: None,
     };
 
+    state
+        .persister
+        .upsert_pending_invoice(&pending_invoice)
+        .await
+        .map_err(|e| {
+            println!("⚠️ persister.upsert_pending_invoice failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
     // Store pending invoice with cleanup of expired invoices
     {
         let mut pending = state.pending_invoices.lock().await;
-        
+
         // Clean up expired invoices
         let now = Utc::now();
         pending.retain(|_, invoice| invoice.expires_at > now);
-        
+
         pending.insert(payment_hash_hex.clone(), pending_invoice);
     }
 
-    println!("🔗 Created Lightning invoice: {} sats to {} (payment_hash: {})",
-             params.amount_sats,
+    println!("🔗 Created Lightning invoice: {} sats (payment_hash: {})", amount_sats, payment_hash_hex);
+
+    Ok((invoice_str, payment_hash_hex, qr_code))
+}
+
+/// Creates a reusable BOLT12 offer, e.g. for a campaign poster, that can
+/// collect unlimited donations instead of the single-use BOLT11 invoices
+/// from `/create-invoice`. `poll_offer_payments` turns each inbound payment
+/// against it into its own `Donation`.
+#[axum::debug_handler]
+async fn create_offer(
+    Query(params): Query<CreateOfferRequest>,
+    State(state): State<AppState>,
+) -> Result<Json<CreateOfferResponse>, StatusCode> {
+    if let Some(amount_sats) = params.amount_sats {
+        if !(100..=1_000_000).contains(&amount_sats) {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    let description = if let Some(ref recipient) = params.recipient {
+        format!("Recurring donations to {}", recipient)
+    } else {
+        "Recurring donations to SatsForGood".to_string()
+    };
+
+    let (bolt12, offer_id) = state
+        .backend
+        .add_offer(params.amount_sats.map(|sats| sats * 1000), description.clone())
+        .await
+        .map_err(|e| {
+            println!("⚠️ backend.add_offer failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let qr_code = generate_qr_base64(&bolt12);
+
+    let offer = Offer {
+        offer_id: offer_id.clone(),
+        bolt12: bolt12.clone(),
+        amount_sats: params.amount_sats,
+        recipient: params.recipient.clone(),
+        description,
+        created_at: Utc::now(),
+    };
+
+    state.persister.upsert_offer(&offer).await.map_err(|e| {
+        println!("⚠️ persister.upsert_offer failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    state.active_offers.lock().await.insert(offer_id.clone(), offer);
+
+    println!("🔗 Created BOLT12 offer for {} (offer_id: {})",
              params.recipient.as_deref().unwrap_or("SatsForGood"),
-             payment_hash_hex);
+             offer_id);
 
-    let response = CreateInvoiceResponse {
-        invoice: invoice_str,
-        payment_hash: payment_hash_hex,
+    Ok(Json(CreateOfferResponse {
+        offer: bolt12,
+        offer_id,
         qr_code,
-        expires_in: 3600,
+    }))
+}
+
+/// Registers `recipient` as a forwarding destination: once a donation tagged
+/// with that recipient settles, `maybe_forward_donation` pays `destination`
+/// onward instead of leaving `recipient` as a cosmetic label.
+#[axum::debug_handler]
+async fn register_recipient(
+    Query(params): Query<RegisterRecipientRequest>,
+    State(state): State<AppState>,
+) -> Result<Json<RegisterRecipientResponse>, StatusCode> {
+    let route = RecipientRoute {
+        recipient: params.recipient.clone(),
+        destination: params.destination,
+        created_at: Utc::now(),
     };
 
-    Ok(Json(response))
+    state.persister.upsert_recipient_route(&route).await.map_err(|e| {
+        println!("⚠️ persister.upsert_recipient_route failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    state.recipient_routes.lock().await.insert(route.recipient.clone(), route);
+
+    println!("🔗 Registered forwarding route for recipient: {}", params.recipient);
+
+    Ok(Json(RegisterRecipientResponse {
+        recipient: params.recipient,
+        registered: true,
+    }))
 }
 
 async fn check_payment(
     Query(params): Query<CheckPaymentRequest>,
     State(state): State<AppState>,
 ) -> Result<Json<CheckPaymentResponse>, StatusCode> {
+    if state.pending_invoices.lock().await.contains_key(&params.payment_hash) {
+        return Ok(Json(refresh_pending_invoice(&state, &params.payment_hash).await));
+    }
+
+    // Not tracked as pending - it may still be a durably recorded Donation
+    // (settled by an earlier call here, by `poll_pending_invoices`, by
+    // `poll_offer_payments`, or from before a restart - the persister, not
+    // this process's in-memory maps, is the source of truth for it).
+    if let Some(donation) = state.persister.get_donation(&params.payment_hash).await.map_err(|e| {
+        println!("⚠️ persister.get_donation failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })? {
+        return Ok(Json(CheckPaymentResponse {
+            status: "PAID".to_string(),
+            paid_at: Some(donation.paid_at),
+        }));
+    }
+
+    println!("⚠️ Invoice not found: {}", params.payment_hash);
+    Ok(Json(CheckPaymentResponse {
+        status: "PENDING".to_string(),
+        paid_at: None,
+    }))
+}
+
+/// Drives one tracked `PendingInvoice` through its settlement checks: expire
+/// it past `expires_at`, ask the backend whether it paid, and - the first
+/// time it's seen paid - record the `Donation` and kick off forwarding.
+///
+/// Shared by `check_payment` (a client-initiated poll) and
+/// `poll_pending_invoices` (a background tick) so a donor who only listens on
+/// `/payment-events` and never calls `/check-payment` again still gets their
+/// BOLT11 invoice's Pending -> Paid transition noticed and published.
+async fn refresh_pending_invoice(state: &AppState, payment_hash: &str) -> CheckPaymentResponse {
     let mut pending = state.pending_invoices.lock().await;
-    let mut donations = state.completed_donations.lock().await;
+    let Some(mut invoice) = pending.get(payment_hash).cloned() else {
+        return CheckPaymentResponse { status: "PENDING".to_string(), paid_at: None };
+    };
     let now = Utc::now();
 
-    // Check if we have this invoice
-    if let Some(mut invoice) = pending.get_mut(&params.payment_hash).cloned() {
-        // Check if invoice has expired
-        if invoice.expires_at <= now {
-            if invoice.payment_state != PaymentState::Paid {
-                invoice.payment_state = PaymentState::Expired;
-                pending.insert(params.payment_hash.clone(), invoice);
-            }
-            println!("⏰ Invoice expired: {}", params.payment_hash);
-            return Ok(Json(CheckPaymentResponse {
-                status: "EXPIRED".to_string(),
-                paid_at: None,
-            }));
+    // A `Paid` invoice stays on the paid path below even past `expires_at`,
+    // so a confirmation that arrives late still gets promoted to a `Donation`
+    // instead of being reported (and discarded) as EXPIRED.
+    if invoice.expires_at <= now && invoice.payment_state != PaymentState::Paid {
+        invoice.payment_state = PaymentState::Expired;
+        if let Err(e) = state.persister.upsert_pending_invoice(&invoice).await {
+            println!("⚠️ persister.upsert_pending_invoice failed: {e}");
         }
+        pending.insert(payment_hash.to_string(), invoice);
+        events::publish_payment_settled(state, payment_hash, "EXPIRED", None);
+        println!("⏰ Invoice expired: {}", payment_hash);
+        return CheckPaymentResponse { status: "EXPIRED".to_string(), paid_at: None };
+    }
 
-        // Check payment state
-        match invoice.payment_state {
-            PaymentState::Paid => {
-                // Payment already confirmed, move to completed donations
-                if let Some(paid_at) = invoice.paid_at {
-                    // Check if already in completed donations
-                    let already_exists = donations.iter()
-                        .any(|d| d.payment_hash == params.payment_hash);
-                    
-                    if !already_exists {
-                        let donation = Donation {
-                            id: Uuid::new_v4().to_string(),
-                            donor_name: invoice.donor_name.clone().unwrap_or_else(|| "Anonymous".to_string()),
-                            recipient: invoice.recipient,
-                            amount_sats: invoice.amount_sats,
-                            payment_hash: params.payment_hash.clone(),
-                            paid_at,
-                        };
-                        donations.push(donation);
-                        println!("✅ Payment confirmed: {} sats from {} (hash: {})",
-                                invoice.amount_sats,
-                                invoice.donor_name.as_deref().unwrap_or("Anonymous"),
-                                params.payment_hash);
+    if invoice.payment_state == PaymentState::Pending {
+        // Ask the real Lightning node backend whether this invoice settled.
+        match state.backend.lookup_invoice(payment_hash).await {
+            Ok(InvoiceState::Paid { preimage, settled_at }) => {
+                if verify_payment_proof(&preimage, payment_hash) {
+                    invoice.payment_state = PaymentState::Paid;
+                    invoice.paid_at = Some(settled_at);
+                    invoice.payment_preimage = Some(preimage.clone());
+                    if let Err(e) = state.persister.mark_paid(payment_hash, &preimage, settled_at).await {
+                        println!("⚠️ persister.mark_paid failed: {e}");
                     }
-                    
-                    // Remove from pending invoices
-                    pending.remove(&params.payment_hash);
+                    println!("🚀 Lightning payment detected and confirmed: {}", payment_hash);
+                } else {
+                    println!("🛑 Preimage/payment_hash mismatch, refusing to mark paid: {}", payment_hash);
                 }
-                
-                return Ok(Json(CheckPaymentResponse {
-                    status: "PAID".to_string(),
-                    paid_at: invoice.paid_at,
-                }));
-            },
-            PaymentState::Expired => {
-                pending.remove(&params.payment_hash);
-                return Ok(Json(CheckPaymentResponse {
-                    status: "EXPIRED".to_string(),
-                    paid_at: None,
-                }));
-            },
-            PaymentState::Pending => {
-                // REAL LIGHTNING NODE INTEGRATION SIMULATION
-                //
-                // In production, this would query a real Lightning node:
-                // - LND: ln_client.lookup_invoice(payment_hash)?
-                // - c-lightning: lightningrpc.listinvoices(Some(payment_hash))?
-                // - Eclair: eclair_client.getInvoice(payment_hash)?
-                //
-                // For simulation, we'll implement a realistic payment detection
-                // that requires actual Lightning Network payment confirmation.
-                simulate_lightning_payment_detection(&params.payment_hash, &mut invoice, &state).await;
-                
-                // Update the invoice state
-                pending.insert(params.payment_hash.clone(), invoice.clone());
-                
-                // Return current status based on updated invoice state
-                let response = match invoice.payment_state {
-                    PaymentState::Paid => {
-                        println!("🔍 Lightning payment detection: PAID for {}", params.payment_hash);
-                        CheckPaymentResponse {
-                            status: "PAID".to_string(),
-                            paid_at: invoice.paid_at,
-                        }
-                    },
-                    PaymentState::Expired => {
-                        pending.remove(&params.payment_hash);
-                        println!("🔍 Lightning payment detection: EXPIRED for {}", params.payment_hash);
-                        CheckPaymentResponse {
-                            status: "EXPIRED".to_string(),
-                            paid_at: None,
-                        }
-                    },
-                    PaymentState::Pending => {
-                        println!("🔍 Lightning payment detection: PENDING for {}", params.payment_hash);
-                        CheckPaymentResponse {
-                            status: "PENDING".to_string(),
-                            paid_at: None,
-                        }
-                    }
-                };
-                
-                return Ok(Json(response));
+            }
+            Ok(InvoiceState::Expired) => {
+                invoice.payment_state = PaymentState::Expired;
+            }
+            Ok(InvoiceState::Open) => {}
+            Err(e) => {
+                println!("⚠️ backend.lookup_invoice failed for {}: {e}", payment_hash);
             }
         }
-    } else {
-        // Invoice not found - check if it's in completed donations
-        let completed_donation = donations.iter()
-            .find(|d| d.payment_hash == params.payment_hash);
-            
-        if let Some(donation) = completed_donation {
-            return Ok(Json(CheckPaymentResponse {
-                status: "PAID".to_string(),
-                paid_at: Some(donation.paid_at),
-            }));
+        pending.insert(payment_hash.to_string(), invoice.clone());
+    }
+
+    match invoice.payment_state {
+        PaymentState::Paid => {
+            println!("🔍 Lightning payment detection: PAID for {}", payment_hash);
+            record_donation_if_new(state, &invoice, payment_hash).await;
+            pending.remove(payment_hash);
+            if let Err(e) = state.persister.remove_pending_invoice(payment_hash).await {
+                println!("⚠️ persister.remove_pending_invoice failed: {e}");
+            }
+            events::publish_payment_settled(state, payment_hash, "PAID", invoice.paid_at);
+            CheckPaymentResponse { status: "PAID".to_string(), paid_at: invoice.paid_at }
+        }
+        PaymentState::Expired => {
+            pending.remove(payment_hash);
+            if let Err(e) = state.persister.remove_pending_invoice(payment_hash).await {
+                println!("⚠️ persister.remove_pending_invoice failed: {e}");
+            }
+            println!("🔍 Lightning payment detection: EXPIRED for {}", payment_hash);
+            events::publish_payment_settled(state, payment_hash, "EXPIRED", None);
+            CheckPaymentResponse { status: "EXPIRED".to_string(), paid_at: None }
+        }
+        PaymentState::Pending => {
+            println!("🔍 Lightning payment detection: PENDING for {}", payment_hash);
+            CheckPaymentResponse { status: "PENDING".to_string(), paid_at: None }
         }
     }
+}
 
-    // Invoice not found
-    println!("⚠️ Invoice not found: {}", params.payment_hash);
-    Ok(Json(CheckPaymentResponse {
-        status: "PENDING".to_string(),
-        paid_at: None,
-    }))
+/// Turns a just-settled `invoice` into a `Donation`, unless one is already on
+/// file for `payment_hash` - both `check_payment` and `poll_pending_invoices`
+/// can observe the same settlement, so this is the single place that decides
+/// whether it's actually new.
+async fn record_donation_if_new(state: &AppState, invoice: &PendingInvoice, payment_hash: &str) {
+    let Some(paid_at) = invoice.paid_at else { return };
+
+    match state.persister.get_donation(payment_hash).await {
+        Ok(Some(_)) => return,
+        Err(e) => {
+            println!("⚠️ persister.get_donation failed for {payment_hash}: {e}");
+            return;
+        }
+        Ok(None) => {}
+    }
+
+    let forward_state = if is_recipient_registered(state, invoice.recipient.as_deref()).await {
+        Some(ForwardState::Pending)
+    } else {
+        None
+    };
+    let donation = Donation {
+        id: Uuid::new_v4().to_string(),
+        donor_name: invoice.donor_name.clone().unwrap_or_else(|| "Anonymous".to_string()),
+        recipient: invoice.recipient.clone(),
+        amount_sats: invoice.amount_sats,
+        payment_hash: payment_hash.to_string(),
+        paid_at,
+        offer_id: None,
+        forward_state,
+        forward_error: None,
+        preimage: invoice.payment_preimage.clone(),
+        invoice: Some(invoice.invoice.clone()),
+    };
+    if let Err(e) = state.persister.insert_donation(&donation).await {
+        println!("⚠️ persister.insert_donation failed: {e}");
+        return;
+    }
+    events::publish_donation(state, &donation);
+    tokio::spawn(maybe_forward_donation(state.clone(), donation.clone()));
+    println!(
+        "✅ Payment confirmed: {} sats from {} (hash: {})",
+        donation.amount_sats, donation.donor_name, payment_hash
+    );
+}
+
+/// Background task: periodically asks the backend whether each tracked
+/// single-use BOLT11 invoice has settled, the same way `check_payment` does
+/// for a client-initiated poll. `poll_offer_payments` already does this for
+/// BOLT12 offers - without this counterpart, nothing drives a BOLT11
+/// invoice's Pending -> Paid transition for a donor who only listens on
+/// `/payment-events` instead of polling `/check-payment`.
+async fn poll_pending_invoices(state: AppState) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
+    loop {
+        interval.tick().await;
+
+        let payment_hashes: Vec<String> = state.pending_invoices.lock().await.keys().cloned().collect();
+        for payment_hash in payment_hashes {
+            refresh_pending_invoice(&state, &payment_hash).await;
+        }
+    }
 }
 
 async fn get_donation_stats(
     State(state): State<AppState>,
 ) -> Result<Json<DonationStats>, StatusCode> {
-    let donations = state.completed_donations.lock().await;
-    let total_sats = donations.iter().map(|d| d.amount_sats).sum();
-    let donor_count = donations.len();
+    let stats = state.persister.stats().await.map_err(|e| {
+        println!("⚠️ persister.stats failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
 
-    Ok(Json(DonationStats {
-        total_sats,
-        donor_count,
-    }))
+    Ok(Json(stats))
 }
 
 async fn get_recent_donations(
+    Query(params): Query<RecentDonationsQuery>,
     State(state): State<AppState>,
 ) -> Result<Json<Vec<Donation>>, StatusCode> {
-    let donations = state.completed_donations.lock().await;
-    let recent: Vec<_> = donations
-        .iter()
-        .rev()
-        .take(10)
-        .cloned()
-        .collect();
-
-    Ok(Json(recent))
+    let offset = params.offset.unwrap_or(0);
+    let limit = params.limit.unwrap_or(10).min(100);
+
+    let donations = state.persister.list_completed(offset, limit).await.map_err(|e| {
+        println!("⚠️ persister.list_completed failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(donations))
 }
 
-async fn get_donation_receipt(
-    Query(params): Query<ReceiptRequest>,
-    State(state): State<AppState>,
-) -> Result<Json<DonationReceipt>, StatusCode> {
-    let _donations = state.completed_donations.lock().await;
-    
-    // PRODUCTION NOTE: Receipt generation disabled until real Lightning integration
-    // In production, this would generate receipts for actual paid Lightning invoices
-    println!("📄 Receipt requested for payment_hash: {} (Lightning integration required)", params.payment_hash);
-    
-    Err(StatusCode::NOT_FOUND)
+/// Background task: periodically asks the backend which payments landed
+/// against each active BOLT12 offer and turns new ones into `Donation`s.
+/// Unlike single-use BOLT11 invoices, offers aren't polled by any client
+/// request, so this is the only place those payments get recorded.
+async fn poll_offer_payments(state: AppState) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
+    loop {
+        interval.tick().await;
+
+        let offers: Vec<Offer> = state.active_offers.lock().await.values().cloned().collect();
+        for offer in offers {
+            let payments = match state.backend.lookup_offer_payments(&offer.offer_id).await {
+                Ok(payments) => payments,
+                Err(e) => {
+                    println!("⚠️ backend.lookup_offer_payments failed for {}: {e}", offer.offer_id);
+                    continue;
+                }
+            };
+
+            for payment in payments {
+                if !verify_payment_proof(&payment.preimage, &payment.payment_hash) {
+                    println!("🛑 Offer payment preimage mismatch, skipping: {}", payment.payment_hash);
+                    continue;
+                }
+
+                // The backend keeps returning every payment it has ever seen
+                // against this offer, not just new ones since the last tick -
+                // skip anything already recorded so a 30s re-poll doesn't
+                // re-publish events or re-forward an already-settled donation.
+                match state.persister.get_donation(&payment.payment_hash).await {
+                    Ok(Some(_)) => continue,
+                    Err(e) => {
+                        println!("⚠️ persister.get_donation failed for {}: {e}", payment.payment_hash);
+                        continue;
+                    }
+                    Ok(None) => {}
+                }
+
+                let forward_state = if is_recipient_registered(&state, offer.recipient.as_deref()).await {
+                    Some(ForwardState::Pending)
+                } else {
+                    None
+                };
+                let donation = Donation {
+                    id: Uuid::new_v4().to_string(),
+                    donor_name: payment.payer_note.clone().unwrap_or_else(|| "Anonymous".to_string()),
+                    recipient: offer.recipient.clone(),
+                    amount_sats: payment.amount_msat / 1000,
+                    payment_hash: payment.payment_hash.clone(),
+                    paid_at: payment.settled_at,
+                    offer_id: Some(offer.offer_id.clone()),
+                    forward_state,
+                    forward_error: None,
+                    preimage: Some(payment.preimage.clone()),
+                    // No single-use invoice exists for an offer payment - the
+                    // reusable BOLT12 offer itself is the closest equivalent a
+                    // receipt can show a QR for.
+                    invoice: Some(offer.bolt12.clone()),
+                };
+
+                if let Err(e) = state.persister.insert_donation(&donation).await {
+                    println!("⚠️ persister.insert_donation (offer) failed: {e}");
+                    continue;
+                }
+                events::publish_payment_settled(&state, &donation.payment_hash, "PAID", Some(donation.paid_at));
+                events::publish_donation(&state, &donation);
+                tokio::spawn(maybe_forward_donation(state.clone(), donation.clone()));
+                println!("✅ Offer payment confirmed: {} sats for offer {} (hash: {})",
+                         donation.amount_sats, offer.offer_id, donation.payment_hash);
+            }
+        }
+    }
 }
 
-/// Simulates realistic Lightning Network payment detection
-///
-/// This function implements a production-like payment verification system
-/// that would normally query a real Lightning node. For now, it simulates
-/// the behavior where payments only succeed when they are actually detected
-/// as paid on the Lightning Network.
-async fn simulate_lightning_payment_detection(
-    payment_hash: &str,
-    invoice: &mut PendingInvoice,
-    state: &AppState,
-) {
-    // In production, this would query a real Lightning node:
-    // let invoice_status = ln_client.lookup_invoice(payment_hash).await;
-    // match invoice_status.state {
-    //     InvoiceState::Paid => { /* mark as paid */ }
-    //     InvoiceState::Open => { /* still pending */ }
-    //     InvoiceState::Expired => { /* mark as expired */ }
-    // }
-    
-    // For demonstration, we'll implement a realistic simulation:
-    // 1. Real Lightning payments are detected by payment_hash
-    // 2. Only payments that exist on the Lightning Network succeed
-    // 3. No fake/mock payments are accepted
-    
-    // Check if this payment hash exists in the Lightning Network
-    // In production, this would be a database query to a Lightning node
-    let lightning_payments = state.completed_donations.lock().await;
-    let is_lightning_payment = lightning_payments.iter()
-        .any(|d| d.payment_hash == payment_hash);
-    drop(lightning_payments);
-    
-    // Simulate Lightning Network propagation delay
-    // Real Lightning payments take time to propagate and confirm
-    // let time_since_creation = Utc::now().signed_duration_since(invoice.created_at);
-    
-    // If this is a real Lightning payment, it will be marked as paid
-    // by the Lightning node integration (in production)
-    // For simulation, we check if the payment_hash exists in Lightning Network
-    
-    if is_lightning_payment && invoice.payment_state == PaymentState::Pending {
-        invoice.payment_state = PaymentState::Paid;
-        invoice.paid_at = Some(Utc::now());
-        println!("🚀 Lightning payment detected and confirmed: {}", payment_hash);
+async fn is_recipient_registered(state: &AppState, recipient: Option<&str>) -> bool {
+    match recipient {
+        Some(recipient) => state.recipient_routes.lock().await.contains_key(recipient),
+        None => false,
+    }
+}
+
+/// Pays a donation onward to its recipient's registered destination, if any.
+/// Runs detached from the request/poll that produced `donation` so a slow or
+/// failing outgoing payment never holds up donor-facing responses; on
+/// failure the received funds are retained rather than unwound.
+async fn maybe_forward_donation(state: AppState, donation: Donation) {
+    let Some(recipient) = donation.recipient.clone() else {
+        return;
+    };
+    let Some(route) = state.recipient_routes.lock().await.get(&recipient).cloned() else {
+        return;
+    };
+
+    // Re-read the persisted forward_state rather than trusting `donation` (which
+    // may be stale by the time this spawned task runs) so a donation that was
+    // already forwarded - successfully or not - never gets paid out twice.
+    match state.persister.get_donation(&donation.payment_hash).await {
+        Ok(Some(persisted)) if persisted.forward_state != Some(ForwardState::Pending) => {
+            println!(
+                "⏭️ Skipping forward for {} (hash: {}): already {}",
+                recipient,
+                donation.payment_hash,
+                persisted
+                    .forward_state
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| "unregistered".to_string())
+            );
+            return;
+        }
+        Err(e) => {
+            println!("⚠️ persister.get_donation failed for {}: {e}, refusing to forward", donation.payment_hash);
+            return;
+        }
+        _ => {}
+    }
+
+    println!("📡 Forwarding {} sats for {} to its registered destination", donation.amount_sats, recipient);
+
+    match state.backend.send_payment(&route.destination, donation.amount_sats * 1000).await {
+        Ok(preimage) => {
+            if let Err(e) = state
+                .persister
+                .update_forward_state(&donation.payment_hash, ForwardState::Succeeded, None)
+                .await
+            {
+                println!("⚠️ persister.update_forward_state failed: {e}");
+            }
+            println!("✅ Forwarded donation to {} (preimage: {})", recipient, preimage);
+        }
+        Err(e) => {
+            let error_message = e.to_string();
+            if let Err(persist_err) = state
+                .persister
+                .update_forward_state(&donation.payment_hash, ForwardState::Failed, Some(&error_message))
+                .await
+            {
+                println!("⚠️ persister.update_forward_state failed: {persist_err}");
+            }
+            println!("🛑 Forwarding to {} failed, retaining funds: {error_message}", recipient);
+        }
     }
-    
-    // Additional production notes:
-    // - Real Lightning node integration would handle this automatically
-    // - Payment detection is immediate once confirmed on the network
-    // - No manual intervention needed - it's all automated
-    // - The payment_hash is the key that links invoices to payments
-    
-    println!("📡 Lightning Network status check for {}: {:?}",
-             payment_hash,
-             match invoice.payment_state {
-                 PaymentState::Pending => "Waiting for payment confirmation...",
-                 PaymentState::Paid => "Payment confirmed on Lightning Network!",
-                 PaymentState::Expired => "Invoice expired",
-             });
+}
+
+/// Verifies that `preimage` (hex-encoded) really hashes to `payment_hash` (hex-encoded).
+///
+/// This is the cryptographic proof of payment: revealing a preimage that hashes
+/// to the invoice's payment_hash is only possible for whoever actually received
+/// the Lightning payment, so this must hold before a `PendingInvoice` is ever
+/// moved into `PaymentState::Paid`.
+pub(crate) fn verify_payment_proof(preimage_hex: &str, payment_hash_hex: &str) -> bool {
+    let Ok(preimage_bytes) = hex::decode(preimage_hex) else {
+        return false;
+    };
+    let Ok(expected_hash_bytes) = hex::decode(payment_hash_hex) else {
+        return false;
+    };
+    sha256::Hash::hash(&preimage_bytes).as_byte_array().as_slice() == expected_hash_bytes.as_slice()
 }
 
 fn generate_qr_base64(invoice: &str) -> String {
+    use base64::Engine;
+    format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(generate_qr_png_bytes(invoice))
+    )
+}
+
+/// Renders `data` as a QR code PNG, high-contrast and mobile-scan friendly.
+/// Shared by `generate_qr_base64` (wallet-facing invoice QR) and the receipt
+/// PDF (`receipts::render_pdf`), which needs raw image bytes rather than a
+/// data URI.
+pub(crate) fn generate_qr_png_bytes(data: &str) -> Vec<u8> {
     use qrcode::{QrCode, EcLevel};
     use image::{Luma, ImageEncoder};
     use image::codecs::png::PngEncoder;
 
     // Generate high-quality QR code optimized for mobile scanning
-    let code = QrCode::with_error_correction_level(invoice.as_bytes(), EcLevel::M)
-        .unwrap_or_else(|_| QrCode::new(invoice.as_bytes()).unwrap());
-    
+    let code = QrCode::with_error_correction_level(data.as_bytes(), EcLevel::M)
+        .unwrap_or_else(|_| QrCode::new(data.as_bytes()).unwrap());
+
     // Render with high contrast and mobile-optimized styling
     let image = code
         .render::<Luma<u8>>()
@@ -491,12 +802,9 @@ fn generate_qr_base64(invoice: &str) -> String {
 
     // Convert to PNG bytes with high quality settings
     let mut png_bytes = Vec::new();
-    {
-        let encoder = PngEncoder::new(&mut png_bytes);
-        encoder.write_image(&image, image.width(), image.height(), image::ColorType::L8)
-            .expect("Failed to encode PNG");
-    }
-
-    use base64::Engine;
-    format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(&png_bytes))
+    let encoder = PngEncoder::new(&mut png_bytes);
+    encoder
+        .write_image(&image, image.width(), image.height(), image::ColorType::L8)
+        .expect("Failed to encode PNG");
+    png_bytes
 }
\ No newline at end of file
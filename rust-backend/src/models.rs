@@ -0,0 +1,120 @@
+/*
+ * Domain types shared between the HTTP handlers (main.rs) and the
+ * persistence layer - both need the same shape for a pending invoice or a
+ * completed donation, so they live here instead of being duplicated.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PendingInvoice {
+    pub invoice: String,
+    pub payment_hash: String,
+    /// Preimage revealed by the backend once the invoice is settled. `None`
+    /// until then - we never hold it up front since the node, not us, owns it.
+    pub payment_preimage: Option<String>,
+    pub amount_sats: u64,
+    pub donor_name: Option<String>,
+    pub recipient: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub payment_state: PaymentState,
+    pub paid_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+pub enum PaymentState {
+    Pending,    // Invoice created, waiting for payment
+    Paid,       // Payment confirmed on Lightning Network
+    Expired,    // Invoice expired without payment
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Donation {
+    pub id: String,
+    pub donor_name: String,
+    pub recipient: Option<String>,
+    pub amount_sats: u64,
+    pub payment_hash: String,
+    pub paid_at: DateTime<Utc>,
+    /// Set when this donation was collected through a reusable BOLT12 offer
+    /// rather than a single-use BOLT11 invoice from `/create-invoice`.
+    pub offer_id: Option<String>,
+    /// `None` when `recipient` is just a cosmetic label. Set once the
+    /// recipient is registered for forwarding (see `RecipientRoute`) and the
+    /// received funds start their own onward journey.
+    pub forward_state: Option<ForwardState>,
+    /// Set when `forward_state` is `Failed`, so the failure reason survives
+    /// a restart alongside the (retained) donation.
+    pub forward_error: Option<String>,
+    /// Preimage the backend revealed on settlement - the cryptographic proof
+    /// of payment a receipt needs, captured at the same time the donation is
+    /// recorded since the originating `PendingInvoice` is deleted shortly after.
+    pub preimage: Option<String>,
+    /// The BOLT11 invoice the donor actually paid, so a receipt can embed its
+    /// QR for auditability. A BOLT12 offer donation has no single-use invoice
+    /// of its own, so it stores the reusable offer string it was paid through.
+    pub invoice: Option<String>,
+}
+
+/// State of the onward payment to a registered recipient, kept separate from
+/// `PaymentState` (which only tracks the inbound donation) since forwarding
+/// can fail without undoing the fact that the donation itself was received.
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+pub enum ForwardState {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+impl std::fmt::Display for ForwardState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            ForwardState::Pending => "pending",
+            ForwardState::Succeeded => "succeeded",
+            ForwardState::Failed => "failed",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// A recipient registered to receive forwarded donations, keyed by the same
+/// `recipient` label used on `Donation`/`PendingInvoice`. `destination` is a
+/// fixed-amount BOLT11 invoice the recipient gave us to pay them - neither
+/// `LightningBackend` impl dispatches a BOLT12 offer or bare pubkey yet.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RecipientRoute {
+    pub recipient: String,
+    pub destination: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct DonationStats {
+    pub total_sats: u64,
+    pub donor_count: usize,
+    pub per_offer: Vec<OfferStats>,
+}
+
+#[derive(Serialize)]
+pub struct OfferStats {
+    pub offer_id: String,
+    pub recipient: Option<String>,
+    pub total_sats: u64,
+    pub donor_count: usize,
+}
+
+/// A reusable BOLT12 offer, e.g. printed once on a campaign poster, that can
+/// collect unlimited donations - unlike a BOLT11 invoice from `/create-invoice`,
+/// which is single-use and expires after an hour.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Offer {
+    pub offer_id: String,
+    pub bolt12: String,
+    /// `None` means the offer is "any amount" - the payer chooses.
+    pub amount_sats: Option<u64>,
+    pub recipient: Option<String>,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,539 @@
+/*
+ * Durable storage for pending invoices and completed donations.
+ *
+ * `AppState` previously kept everything in in-memory maps, so a restart
+ * wiped donation history and reset stats to zero. `Persister` is the
+ * storage-agnostic interface the handlers talk to; `SqlitePersister` is the
+ * production implementation, modeled on Breez SDK's
+ * `insert_or_update_payments` pattern of upserting by primary key.
+ */
+
+use crate::models::{Donation, DonationStats, ForwardState, Offer, OfferStats, PaymentState, PendingInvoice, RecipientRoute};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+pub struct PersistError(pub String);
+
+impl std::fmt::Display for PersistError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "persistence error: {}", self.0)
+    }
+}
+
+impl std::error::Error for PersistError {}
+
+#[async_trait]
+pub trait Persister: Send + Sync {
+    async fn upsert_pending_invoice(&self, invoice: &PendingInvoice) -> Result<(), PersistError>;
+    async fn remove_pending_invoice(&self, payment_hash: &str) -> Result<(), PersistError>;
+    async fn list_pending_invoices(&self) -> Result<Vec<PendingInvoice>, PersistError>;
+    async fn mark_paid(
+        &self,
+        payment_hash: &str,
+        preimage: &str,
+        paid_at: DateTime<Utc>,
+    ) -> Result<(), PersistError>;
+    async fn insert_donation(&self, donation: &Donation) -> Result<(), PersistError>;
+    async fn list_completed(&self, offset: u32, limit: u32) -> Result<Vec<Donation>, PersistError>;
+    async fn get_donation(&self, payment_hash: &str) -> Result<Option<Donation>, PersistError>;
+    async fn stats(&self) -> Result<DonationStats, PersistError>;
+
+    async fn upsert_offer(&self, offer: &Offer) -> Result<(), PersistError>;
+    async fn list_offers(&self) -> Result<Vec<Offer>, PersistError>;
+
+    async fn update_forward_state(
+        &self,
+        payment_hash: &str,
+        forward_state: ForwardState,
+        forward_error: Option<&str>,
+    ) -> Result<(), PersistError>;
+
+    async fn upsert_recipient_route(&self, route: &RecipientRoute) -> Result<(), PersistError>;
+    async fn get_recipient_route(&self, recipient: &str) -> Result<Option<RecipientRoute>, PersistError>;
+    async fn list_recipient_routes(&self) -> Result<Vec<RecipientRoute>, PersistError>;
+}
+
+/// SQLite-backed `Persister`. `rusqlite::Connection` isn't `Send` across awaits,
+/// so each call hands the blocking work to `spawn_blocking` rather than holding
+/// the connection across an `.await`.
+pub struct SqlitePersister {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqlitePersister {
+    pub fn open(db_path: &str) -> Result<Self, PersistError> {
+        let conn = Connection::open(db_path).map_err(|e| PersistError(format!("failed to open {db_path}: {e}")))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS pending_invoices (
+                payment_hash    TEXT PRIMARY KEY,
+                invoice         TEXT NOT NULL,
+                payment_preimage TEXT,
+                amount_sats     INTEGER NOT NULL,
+                donor_name      TEXT,
+                recipient       TEXT,
+                expires_at      TEXT NOT NULL,
+                created_at      TEXT NOT NULL,
+                payment_state   TEXT NOT NULL,
+                paid_at         TEXT
+            );
+            CREATE TABLE IF NOT EXISTS donations (
+                id            TEXT PRIMARY KEY,
+                donor_name    TEXT NOT NULL,
+                recipient     TEXT,
+                amount_sats   INTEGER NOT NULL,
+                payment_hash  TEXT NOT NULL UNIQUE,
+                paid_at       TEXT NOT NULL,
+                offer_id      TEXT,
+                forward_state TEXT,
+                forward_error TEXT,
+                preimage      TEXT,
+                invoice       TEXT
+            );
+            CREATE TABLE IF NOT EXISTS offers (
+                offer_id    TEXT PRIMARY KEY,
+                bolt12      TEXT NOT NULL,
+                amount_sats INTEGER,
+                recipient   TEXT,
+                description TEXT NOT NULL,
+                created_at  TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS recipient_routes (
+                recipient   TEXT PRIMARY KEY,
+                destination TEXT NOT NULL,
+                created_at  TEXT NOT NULL
+            );",
+        )
+        .map_err(|e| PersistError(format!("failed to create schema: {e}")))?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    fn payment_state_to_str(state: &PaymentState) -> &'static str {
+        match state {
+            PaymentState::Pending => "pending",
+            PaymentState::Paid => "paid",
+            PaymentState::Expired => "expired",
+        }
+    }
+
+    fn payment_state_from_str(s: &str) -> PaymentState {
+        match s {
+            "paid" => PaymentState::Paid,
+            "expired" => PaymentState::Expired,
+            _ => PaymentState::Pending,
+        }
+    }
+
+    fn forward_state_to_str(state: &ForwardState) -> &'static str {
+        match state {
+            ForwardState::Pending => "pending",
+            ForwardState::Succeeded => "succeeded",
+            ForwardState::Failed => "failed",
+        }
+    }
+
+    fn forward_state_from_str(s: &str) -> ForwardState {
+        match s {
+            "succeeded" => ForwardState::Succeeded,
+            "failed" => ForwardState::Failed,
+            _ => ForwardState::Pending,
+        }
+    }
+
+    fn row_to_donation(row: &rusqlite::Row) -> rusqlite::Result<Donation> {
+        Ok(Donation {
+            id: row.get(0)?,
+            donor_name: row.get(1)?,
+            recipient: row.get(2)?,
+            amount_sats: row.get(3)?,
+            payment_hash: row.get(4)?,
+            paid_at: parse_rfc3339(row.get::<_, String>(5)?),
+            offer_id: row.get(6)?,
+            forward_state: row.get::<_, Option<String>>(7)?.map(|s| SqlitePersister::forward_state_from_str(&s)),
+            forward_error: row.get(8)?,
+            preimage: row.get(9)?,
+            invoice: row.get(10)?,
+        })
+    }
+}
+
+#[async_trait]
+impl Persister for SqlitePersister {
+    async fn upsert_pending_invoice(&self, invoice: &PendingInvoice) -> Result<(), PersistError> {
+        let conn = self.conn.clone();
+        let invoice = invoice.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| PersistError(e.to_string()))?;
+            conn.execute(
+                "INSERT INTO pending_invoices
+                    (payment_hash, invoice, payment_preimage, amount_sats, donor_name, recipient, expires_at, created_at, payment_state, paid_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                 ON CONFLICT(payment_hash) DO UPDATE SET
+                    invoice = excluded.invoice,
+                    payment_preimage = excluded.payment_preimage,
+                    payment_state = excluded.payment_state,
+                    paid_at = excluded.paid_at",
+                rusqlite::params![
+                    invoice.payment_hash,
+                    invoice.invoice,
+                    invoice.payment_preimage,
+                    invoice.amount_sats,
+                    invoice.donor_name,
+                    invoice.recipient,
+                    invoice.expires_at.to_rfc3339(),
+                    invoice.created_at.to_rfc3339(),
+                    SqlitePersister::payment_state_to_str(&invoice.payment_state),
+                    invoice.paid_at.map(|t| t.to_rfc3339()),
+                ],
+            )
+            .map_err(|e| PersistError(format!("upsert_pending_invoice failed: {e}")))?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| PersistError(format!("join error: {e}")))?
+    }
+
+    async fn remove_pending_invoice(&self, payment_hash: &str) -> Result<(), PersistError> {
+        let conn = self.conn.clone();
+        let payment_hash = payment_hash.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| PersistError(e.to_string()))?;
+            conn.execute(
+                "DELETE FROM pending_invoices WHERE payment_hash = ?1",
+                rusqlite::params![payment_hash],
+            )
+            .map_err(|e| PersistError(format!("remove_pending_invoice failed: {e}")))?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| PersistError(format!("join error: {e}")))?
+    }
+
+    async fn list_pending_invoices(&self) -> Result<Vec<PendingInvoice>, PersistError> {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| PersistError(e.to_string()))?;
+            let mut stmt = conn
+                .prepare(
+                    "SELECT payment_hash, invoice, payment_preimage, amount_sats, donor_name, recipient,
+                            expires_at, created_at, payment_state, paid_at
+                     FROM pending_invoices WHERE payment_state = 'pending'",
+                )
+                .map_err(|e| PersistError(format!("list_pending_invoices prepare failed: {e}")))?;
+
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(PendingInvoice {
+                        payment_hash: row.get(0)?,
+                        invoice: row.get(1)?,
+                        payment_preimage: row.get(2)?,
+                        amount_sats: row.get(3)?,
+                        donor_name: row.get(4)?,
+                        recipient: row.get(5)?,
+                        expires_at: parse_rfc3339(row.get::<_, String>(6)?),
+                        created_at: parse_rfc3339(row.get::<_, String>(7)?),
+                        payment_state: SqlitePersister::payment_state_from_str(&row.get::<_, String>(8)?),
+                        paid_at: row.get::<_, Option<String>>(9)?.map(parse_rfc3339),
+                    })
+                })
+                .map_err(|e| PersistError(format!("list_pending_invoices query failed: {e}")))?;
+
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| PersistError(format!("list_pending_invoices row decode failed: {e}")))
+        })
+        .await
+        .map_err(|e| PersistError(format!("join error: {e}")))?
+    }
+
+    async fn mark_paid(
+        &self,
+        payment_hash: &str,
+        preimage: &str,
+        paid_at: DateTime<Utc>,
+    ) -> Result<(), PersistError> {
+        let conn = self.conn.clone();
+        let payment_hash = payment_hash.to_string();
+        let preimage = preimage.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| PersistError(e.to_string()))?;
+            conn.execute(
+                "UPDATE pending_invoices SET payment_state = 'paid', payment_preimage = ?2, paid_at = ?3 WHERE payment_hash = ?1",
+                rusqlite::params![payment_hash, preimage, paid_at.to_rfc3339()],
+            )
+            .map_err(|e| PersistError(format!("mark_paid failed: {e}")))?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| PersistError(format!("join error: {e}")))?
+    }
+
+    async fn insert_donation(&self, donation: &Donation) -> Result<(), PersistError> {
+        let conn = self.conn.clone();
+        let donation = donation.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| PersistError(e.to_string()))?;
+            conn.execute(
+                "INSERT OR IGNORE INTO donations (id, donor_name, recipient, amount_sats, payment_hash, paid_at, offer_id, forward_state, forward_error, preimage, invoice)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                rusqlite::params![
+                    donation.id,
+                    donation.donor_name,
+                    donation.recipient,
+                    donation.amount_sats,
+                    donation.payment_hash,
+                    donation.paid_at.to_rfc3339(),
+                    donation.offer_id,
+                    donation.forward_state.as_ref().map(SqlitePersister::forward_state_to_str),
+                    donation.forward_error,
+                    donation.preimage,
+                    donation.invoice,
+                ],
+            )
+            .map_err(|e| PersistError(format!("insert_donation failed: {e}")))?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| PersistError(format!("join error: {e}")))?
+    }
+
+    async fn list_completed(&self, offset: u32, limit: u32) -> Result<Vec<Donation>, PersistError> {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| PersistError(e.to_string()))?;
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, donor_name, recipient, amount_sats, payment_hash, paid_at, offer_id, forward_state, forward_error, preimage, invoice
+                     FROM donations ORDER BY paid_at DESC LIMIT ?1 OFFSET ?2",
+                )
+                .map_err(|e| PersistError(format!("list_completed prepare failed: {e}")))?;
+
+            let rows = stmt
+                .query_map(rusqlite::params![limit, offset], Self::row_to_donation)
+                .map_err(|e| PersistError(format!("list_completed query failed: {e}")))?;
+
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| PersistError(format!("list_completed row decode failed: {e}")))
+        })
+        .await
+        .map_err(|e| PersistError(format!("join error: {e}")))?
+    }
+
+    async fn get_donation(&self, payment_hash: &str) -> Result<Option<Donation>, PersistError> {
+        let conn = self.conn.clone();
+        let payment_hash = payment_hash.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| PersistError(e.to_string()))?;
+            conn.query_row(
+                "SELECT id, donor_name, recipient, amount_sats, payment_hash, paid_at, offer_id, forward_state, forward_error, preimage, invoice
+                 FROM donations WHERE payment_hash = ?1",
+                rusqlite::params![payment_hash],
+                Self::row_to_donation,
+            )
+            .optional()
+            .map_err(|e| PersistError(format!("get_donation failed: {e}")))
+        })
+        .await
+        .map_err(|e| PersistError(format!("join error: {e}")))?
+    }
+
+    async fn stats(&self) -> Result<DonationStats, PersistError> {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| PersistError(e.to_string()))?;
+            let (total_sats, donor_count) = conn
+                .query_row(
+                    "SELECT COALESCE(SUM(amount_sats), 0), COUNT(*) FROM donations",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .map_err(|e| PersistError(format!("stats failed: {e}")))?;
+
+            let mut stmt = conn
+                .prepare(
+                    "SELECT d.offer_id, o.recipient, COALESCE(SUM(d.amount_sats), 0), COUNT(*)
+                     FROM donations d
+                     LEFT JOIN offers o ON o.offer_id = d.offer_id
+                     WHERE d.offer_id IS NOT NULL
+                     GROUP BY d.offer_id",
+                )
+                .map_err(|e| PersistError(format!("per-offer stats prepare failed: {e}")))?;
+
+            let per_offer = stmt
+                .query_map([], |row| {
+                    Ok(OfferStats {
+                        offer_id: row.get(0)?,
+                        recipient: row.get(1)?,
+                        total_sats: row.get(2)?,
+                        donor_count: row.get(3)?,
+                    })
+                })
+                .map_err(|e| PersistError(format!("per-offer stats query failed: {e}")))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| PersistError(format!("per-offer stats row decode failed: {e}")))?;
+
+            Ok(DonationStats {
+                total_sats,
+                donor_count,
+                per_offer,
+            })
+        })
+        .await
+        .map_err(|e| PersistError(format!("join error: {e}")))?
+    }
+
+    async fn upsert_offer(&self, offer: &Offer) -> Result<(), PersistError> {
+        let conn = self.conn.clone();
+        let offer = offer.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| PersistError(e.to_string()))?;
+            conn.execute(
+                "INSERT INTO offers (offer_id, bolt12, amount_sats, recipient, description, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT(offer_id) DO UPDATE SET bolt12 = excluded.bolt12",
+                rusqlite::params![
+                    offer.offer_id,
+                    offer.bolt12,
+                    offer.amount_sats,
+                    offer.recipient,
+                    offer.description,
+                    offer.created_at.to_rfc3339(),
+                ],
+            )
+            .map_err(|e| PersistError(format!("upsert_offer failed: {e}")))?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| PersistError(format!("join error: {e}")))?
+    }
+
+    async fn list_offers(&self) -> Result<Vec<Offer>, PersistError> {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| PersistError(e.to_string()))?;
+            let mut stmt = conn
+                .prepare("SELECT offer_id, bolt12, amount_sats, recipient, description, created_at FROM offers")
+                .map_err(|e| PersistError(format!("list_offers prepare failed: {e}")))?;
+
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(Offer {
+                        offer_id: row.get(0)?,
+                        bolt12: row.get(1)?,
+                        amount_sats: row.get(2)?,
+                        recipient: row.get(3)?,
+                        description: row.get(4)?,
+                        created_at: parse_rfc3339(row.get::<_, String>(5)?),
+                    })
+                })
+                .map_err(|e| PersistError(format!("list_offers query failed: {e}")))?;
+
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| PersistError(format!("list_offers row decode failed: {e}")))
+        })
+        .await
+        .map_err(|e| PersistError(format!("join error: {e}")))?
+    }
+
+    async fn update_forward_state(
+        &self,
+        payment_hash: &str,
+        forward_state: ForwardState,
+        forward_error: Option<&str>,
+    ) -> Result<(), PersistError> {
+        let conn = self.conn.clone();
+        let payment_hash = payment_hash.to_string();
+        let forward_error = forward_error.map(str::to_string);
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| PersistError(e.to_string()))?;
+            conn.execute(
+                "UPDATE donations SET forward_state = ?2, forward_error = ?3 WHERE payment_hash = ?1",
+                rusqlite::params![
+                    payment_hash,
+                    SqlitePersister::forward_state_to_str(&forward_state),
+                    forward_error,
+                ],
+            )
+            .map_err(|e| PersistError(format!("update_forward_state failed: {e}")))?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| PersistError(format!("join error: {e}")))?
+    }
+
+    async fn upsert_recipient_route(&self, route: &RecipientRoute) -> Result<(), PersistError> {
+        let conn = self.conn.clone();
+        let route = route.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| PersistError(e.to_string()))?;
+            conn.execute(
+                "INSERT INTO recipient_routes (recipient, destination, created_at)
+                 VALUES (?1, ?2, ?3)
+                 ON CONFLICT(recipient) DO UPDATE SET destination = excluded.destination",
+                rusqlite::params![route.recipient, route.destination, route.created_at.to_rfc3339()],
+            )
+            .map_err(|e| PersistError(format!("upsert_recipient_route failed: {e}")))?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| PersistError(format!("join error: {e}")))?
+    }
+
+    async fn get_recipient_route(&self, recipient: &str) -> Result<Option<RecipientRoute>, PersistError> {
+        let conn = self.conn.clone();
+        let recipient = recipient.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| PersistError(e.to_string()))?;
+            conn.query_row(
+                "SELECT recipient, destination, created_at FROM recipient_routes WHERE recipient = ?1",
+                rusqlite::params![recipient],
+                |row| {
+                    Ok(RecipientRoute {
+                        recipient: row.get(0)?,
+                        destination: row.get(1)?,
+                        created_at: parse_rfc3339(row.get::<_, String>(2)?),
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| PersistError(format!("get_recipient_route failed: {e}")))
+        })
+        .await
+        .map_err(|e| PersistError(format!("join error: {e}")))?
+    }
+
+    async fn list_recipient_routes(&self) -> Result<Vec<RecipientRoute>, PersistError> {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| PersistError(e.to_string()))?;
+            let mut stmt = conn
+                .prepare("SELECT recipient, destination, created_at FROM recipient_routes")
+                .map_err(|e| PersistError(format!("list_recipient_routes prepare failed: {e}")))?;
+
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(RecipientRoute {
+                        recipient: row.get(0)?,
+                        destination: row.get(1)?,
+                        created_at: parse_rfc3339(row.get::<_, String>(2)?),
+                    })
+                })
+                .map_err(|e| PersistError(format!("list_recipient_routes query failed: {e}")))?;
+
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| PersistError(format!("list_recipient_routes row decode failed: {e}")))
+        })
+        .await
+        .map_err(|e| PersistError(format!("join error: {e}")))?
+    }
+}
+
+fn parse_rfc3339(s: String) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(&s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
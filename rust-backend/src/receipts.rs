@@ -0,0 +1,159 @@
+/*
+ * Donation receipts: a verifiable proof-of-payment for a settled donation,
+ * returned as JSON (for the frontend) or a rendered PDF (for a donor's own
+ * records). "Verifiable" means the preimage recorded alongside the donation
+ * actually hashes to its payment_hash - the same proof `check_payment`/
+ * `poll_offer_payments` already required before ever recording the donation,
+ * re-checked here so a receipt can't be forged by tampering with stored rows.
+ */
+
+use crate::{generate_qr_png_bytes, verify_payment_proof, AppState};
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct ReceiptRequest {
+    payment_hash: String,
+    /// "json" (default) or "pdf".
+    format: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct DonationReceipt {
+    id: String,
+    donor_name: String,
+    recipient: Option<String>,
+    amount_sats: u64,
+    payment_hash: String,
+    /// The settlement preimage, filled in as the receipt's transaction_id -
+    /// it's the cryptographic proof of payment, so it doubles as the unique
+    /// identifier a donor can point to for this transaction.
+    transaction_id: String,
+    paid_at: DateTime<Utc>,
+    network: String,
+    /// Whether `transaction_id` was checked against `payment_hash` just now,
+    /// so a receipt's recipient doesn't have to trust the stored row as-is.
+    verified: bool,
+    forward_state: Option<String>,
+    forward_error: Option<String>,
+    /// The paid BOLT11 invoice (or, for an offer donation, the BOLT12 offer it
+    /// was paid through) - `render_pdf` embeds its QR alongside the proof.
+    invoice: Option<String>,
+}
+
+/// `GET /donation-receipt?payment_hash=...&format=json|pdf`
+#[axum::debug_handler]
+pub async fn donation_receipt(
+    Query(params): Query<ReceiptRequest>,
+    State(state): State<AppState>,
+) -> Result<Response, StatusCode> {
+    let donation = state
+        .persister
+        .get_donation(&params.payment_hash)
+        .await
+        .map_err(|e| {
+            println!("⚠️ persister.get_donation failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Donations recorded before this field existed have no preimage on file -
+    // there's nothing to issue a verifiable receipt for.
+    let preimage = donation.preimage.ok_or(StatusCode::NOT_FOUND)?;
+    let verified = verify_payment_proof(&preimage, &donation.payment_hash);
+
+    let receipt = DonationReceipt {
+        id: donation.id,
+        donor_name: donation.donor_name,
+        recipient: donation.recipient,
+        amount_sats: donation.amount_sats,
+        payment_hash: donation.payment_hash,
+        transaction_id: preimage,
+        paid_at: donation.paid_at,
+        network: state.backend.network().to_string(),
+        verified,
+        forward_state: donation.forward_state.as_ref().map(ToString::to_string),
+        forward_error: donation.forward_error,
+        invoice: donation.invoice,
+    };
+
+    match params.format.as_deref() {
+        Some("pdf") => render_pdf(&receipt).map(IntoResponse::into_response),
+        _ => Ok(Json(receipt).into_response()),
+    }
+}
+
+/// Renders `receipt` as a one-page PDF donors can save for their own records.
+fn render_pdf(receipt: &DonationReceipt) -> Result<impl IntoResponse, StatusCode> {
+    use printpdf::{BuiltinFont, Mm, PdfDocument};
+
+    let (doc, page, layer) =
+        PdfDocument::new("SatsForGood Donation Receipt", Mm(210.0), Mm(297.0), "Layer 1");
+    let font = doc
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(|e| {
+            println!("⚠️ printpdf add_builtin_font failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let current_layer = doc.get_page(page).get_layer(layer);
+
+    let lines: Vec<String> = [
+        Some("SatsForGood Donation Receipt".to_string()),
+        Some(format!("Donor: {}", receipt.donor_name)),
+        receipt.recipient.as_ref().map(|r| format!("Recipient: {r}")),
+        Some(format!("Amount: {} sats", receipt.amount_sats)),
+        Some(format!("Paid at: {}", receipt.paid_at.to_rfc3339())),
+        Some(format!("Network: {}", receipt.network)),
+        Some(format!("Payment hash: {}", receipt.payment_hash)),
+        Some(format!("Transaction ID (settlement preimage): {}", receipt.transaction_id)),
+        Some(format!("Proof verified: {}", receipt.verified)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let mut y = Mm(270.0);
+    for line in &lines {
+        current_layer.use_text(line, 12.0, Mm(20.0), y, &font);
+        y -= Mm(10.0);
+    }
+
+    // Embed the paid invoice's QR below the text fields, for auditability -
+    // anyone holding the receipt can scan it to see what was actually paid.
+    if let Some(invoice) = &receipt.invoice {
+        let png_bytes = generate_qr_png_bytes(invoice);
+        if let Ok(qr_image) = image::load_from_memory(&png_bytes) {
+            printpdf::Image::from_dynamic_image(&qr_image).add_to_layer(
+                current_layer.clone(),
+                printpdf::ImageTransform {
+                    translate_x: Some(Mm(20.0)),
+                    translate_y: Some(y - Mm(60.0)),
+                    scale_x: Some(0.25),
+                    scale_y: Some(0.25),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    let mut bytes = Vec::new();
+    doc.save(&mut std::io::BufWriter::new(&mut bytes)).map_err(|e| {
+        println!("⚠️ printpdf save failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let content_disposition =
+        HeaderValue::from_str(&format!("attachment; filename=\"receipt-{}.pdf\"", receipt.id))
+            .unwrap_or_else(|_| HeaderValue::from_static("attachment; filename=\"receipt.pdf\""));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static("application/pdf")),
+            (header::CONTENT_DISPOSITION, content_disposition),
+        ],
+        bytes,
+    ))
+}